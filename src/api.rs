@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: MIT
+
+//! The JSON-RPC api.
+//!
+//! The api exposes fee estimation and the mempool minimum relay fee from the
+//! configured block source, so clients syncing via this bridge - who have no
+//! full node of their own - can get fee data from the same endpoint. It
+//! answers only those fee methods; no method serves Utreexo data, and the
+//! `sender` channel to the prover is held in shared state but not read.
+
+use std::net::SocketAddr;
+use std::sync::Mutex;
+
+use actix_web::web;
+use actix_web::App;
+use actix_web::HttpResponse;
+use actix_web::HttpServer;
+use futures::channel::mpsc::Sender;
+use serde::Deserialize;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::chaininterface::fee_estimates;
+use crate::chaininterface::Blockchain;
+
+/// A minimal JSON-RPC request envelope.
+#[derive(Debug, Deserialize)]
+struct JsonRpcRequest {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// Starts the api, serving the fee methods from `source` on a single JSON-RPC
+/// route. `sender` is stored in the app data; no route uses it.
+pub async fn create_api<S, M>(
+    sender: Sender<M>,
+    address: SocketAddr,
+    source: S,
+) -> std::io::Result<()>
+where
+    S: Blockchain + Send + Sync + 'static,
+    M: Send + 'static,
+{
+    let source = web::Data::new(source);
+    // Kept in shared state; `handle_rpc` receives it but sends nothing to the
+    // prover over this channel.
+    let sender = web::Data::new(Mutex::new(sender));
+    HttpServer::new(move || {
+        App::new()
+            .app_data(source.clone())
+            .app_data(sender.clone())
+            .route("/", web::post().to(handle_rpc::<S, M>))
+    })
+    .bind(address)?
+    .run()
+    .await
+}
+
+/// Dispatches a single JSON-RPC call.
+async fn handle_rpc<S, M>(
+    req: web::Json<JsonRpcRequest>,
+    source: web::Data<S>,
+    _sender: web::Data<Mutex<Sender<M>>>,
+) -> HttpResponse
+where
+    S: Blockchain + Send + Sync + 'static,
+    M: Send + 'static,
+{
+    let id = req.id.clone();
+    match req.method.as_str() {
+        // Fee estimation and the mempool minimum relay fee, the
+        // `estimatesmartfee` equivalent this bridge exposes to clients.
+        "estimatesmartfee" | "getfeeestimates" | "getmempoolinfo" => {
+            match fee_estimates(source.get_ref()) {
+                Ok(estimates) => result(id, json!(estimates)),
+                Err(e) => error(id, format!("{e}")),
+            }
+        }
+        other => error(id, format!("unknown method: {other}")),
+    }
+}
+
+/// Builds a JSON-RPC success response.
+fn result(id: Value, value: Value) -> HttpResponse {
+    HttpResponse::Ok().json(json!({ "jsonrpc": "2.0", "id": id, "result": value }))
+}
+
+/// Builds a JSON-RPC error response.
+fn error(id: Value, message: String) -> HttpResponse {
+    HttpResponse::Ok().json(json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": -1, "message": message },
+    }))
+}
@@ -0,0 +1,236 @@
+// SPDX-License-Identifier: MIT
+
+//! BIP157/158 compact block filters.
+//!
+//! The `node` serves raw blocks to peers; this module lets it also serve the
+//! much smaller Golomb-Coded Set filters so light clients can scan the chain
+//! without downloading full blocks. We build one basic filter per block over
+//! every output scriptPubKey and every scriptPubKey of the inputs' spent
+//! outputs, maintain the filter-header chain, and persist both in a kv store
+//! next to `BlocksIndex`.
+
+use std::collections::BTreeSet;
+
+use bitcoin::consensus::encode::VarInt;
+use bitcoin::consensus::Encodable;
+use bitcoin::hashes::sha256d;
+use bitcoin::hashes::siphash24;
+use bitcoin::hashes::Hash;
+use bitcoin::Block;
+use bitcoin::BlockHash;
+
+/// Golomb-Rice parameter for basic filters (BIP158).
+const P: u8 = 19;
+/// Range multiplier for basic filters (BIP158).
+const M: u64 = 784931;
+
+/// A serialized basic block filter, ready to be sent in a `cfilter` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockFilter {
+    /// The filter content: a CompactSize element count followed by the
+    /// Golomb-Rice coded set.
+    pub content: Vec<u8>,
+}
+
+impl BlockFilter {
+    /// Builds the basic filter for `block`. `prevouts` holds the scriptPubKey
+    /// of every output spent by the block's inputs (coinbase excluded), which
+    /// the caller resolves from the utxo set.
+    pub fn new(block: &Block, prevouts: &[Vec<u8>]) -> Self {
+        // BIP158 codes the *set* of scripts, so N counts unique elements; a
+        // BTreeSet both dedups and (incidentally) keeps them ordered. Outputs
+        // whose script is empty or an OP_RETURN are excluded.
+        let mut elements: BTreeSet<Vec<u8>> = BTreeSet::new();
+        for tx in &block.txdata {
+            for out in &tx.output {
+                if out.script_pubkey.is_empty() || out.script_pubkey.is_op_return() {
+                    continue;
+                }
+                elements.insert(out.script_pubkey.as_bytes().to_vec());
+            }
+        }
+        // Spent-output scripts can't be OP_RETURN (it's unspendable), so only
+        // the empty-script guard applies here.
+        for script in prevouts {
+            if !script.is_empty() {
+                elements.insert(script.clone());
+            }
+        }
+        let refs: Vec<&[u8]> = elements.iter().map(Vec::as_slice).collect();
+        Self::from_elements(&block.block_hash(), &refs)
+    }
+
+    /// The low-level constructor: maps, sorts and Golomb-Rice codes the given
+    /// set of byte strings, keyed by the block hash.
+    fn from_elements(block_hash: &BlockHash, elements: &[&[u8]]) -> Self {
+        let hash = block_hash.to_byte_array();
+        let k0 = u64::from_le_bytes(hash[0..8].try_into().unwrap());
+        let k1 = u64::from_le_bytes(hash[8..16].try_into().unwrap());
+
+        let n = elements.len() as u64;
+        let modulus = n.saturating_mul(M);
+
+        let mut values: Vec<u64> = elements
+            .iter()
+            .map(|e| map_to_range(siphash24::Hash::hash_to_u64_with_keys(k0, k1, e), modulus))
+            .collect();
+        values.sort_unstable();
+
+        let mut content = Vec::new();
+        VarInt(n).consensus_encode(&mut content).unwrap();
+
+        let mut writer = BitWriter::new(&mut content);
+        let mut last = 0u64;
+        for value in values {
+            let delta = value - last;
+            last = value;
+            writer.write_golomb_rice(delta);
+        }
+        writer.flush();
+
+        BlockFilter { content }
+    }
+
+    /// The filter hash, `sha256d(filter)`.
+    pub fn filter_hash(&self) -> sha256d::Hash {
+        sha256d::Hash::hash(&self.content)
+    }
+
+    /// The filter header, `sha256d(filter_hash || prev_filter_header)`.
+    pub fn filter_header(&self, prev_header: &sha256d::Hash) -> sha256d::Hash {
+        let mut buf = Vec::with_capacity(64);
+        buf.extend_from_slice(&self.filter_hash().to_byte_array());
+        buf.extend_from_slice(&prev_header.to_byte_array());
+        sha256d::Hash::hash(&buf)
+    }
+}
+
+/// Maps a 64-bit hash uniformly into `[0, modulus)` with the 128-bit
+/// multiply-and-shift reduction from BIP158.
+fn map_to_range(hash: u64, modulus: u64) -> u64 {
+    ((hash as u128 * modulus as u128) >> 64) as u64
+}
+
+/// A big-endian bit writer used for Golomb-Rice coding.
+struct BitWriter<'a> {
+    out: &'a mut Vec<u8>,
+    current: u8,
+    bits_used: u8,
+}
+
+impl<'a> BitWriter<'a> {
+    fn new(out: &'a mut Vec<u8>) -> Self {
+        Self {
+            out,
+            current: 0,
+            bits_used: 0,
+        }
+    }
+
+    fn write_bit(&mut self, bit: bool) {
+        if bit {
+            self.current |= 1 << (7 - self.bits_used);
+        }
+        self.bits_used += 1;
+        if self.bits_used == 8 {
+            self.out.push(self.current);
+            self.current = 0;
+            self.bits_used = 0;
+        }
+    }
+
+    /// Writes `value` Golomb-Rice coded with parameter [`P`]: the quotient in
+    /// unary (a run of ones terminated by a zero) followed by the `P`-bit
+    /// remainder.
+    fn write_golomb_rice(&mut self, value: u64) {
+        let quotient = value >> P;
+        for _ in 0..quotient {
+            self.write_bit(true);
+        }
+        self.write_bit(false);
+        for i in (0..P).rev() {
+            self.write_bit((value >> i) & 1 == 1);
+        }
+    }
+
+    /// Pads the final partial byte with zeros and flushes it.
+    fn flush(&mut self) {
+        if self.bits_used > 0 {
+            self.out.push(self.current);
+            self.current = 0;
+            self.bits_used = 0;
+        }
+    }
+}
+
+/// Persists filters and the filter-header chain alongside [`BlocksIndex`].
+///
+/// [`BlocksIndex`]: crate::prove::BlocksIndex
+pub struct FiltersIndex {
+    /// The backing kv store, keyed by block hash.
+    pub database: kv::Store,
+}
+
+impl FiltersIndex {
+    /// The bucket holding serialized filters, keyed by block hash.
+    fn filters(&self) -> kv::Bucket<'_, &[u8], Vec<u8>> {
+        self.database
+            .bucket(Some("cfilters"))
+            .expect("Failed to open cfilters bucket")
+    }
+
+    /// The bucket holding filter headers, keyed by block hash.
+    fn headers(&self) -> kv::Bucket<'_, &[u8], Vec<u8>> {
+        self.database
+            .bucket(Some("cfheaders"))
+            .expect("Failed to open cfheaders bucket")
+    }
+
+    /// Builds the basic filter for `block` over its output and spent-output
+    /// scripts, persists it, and extends the header chain from `prev_header`,
+    /// returning the new filter header. This is the single call `prover.keep_up`
+    /// makes per block as it persists them.
+    pub fn build_and_append(
+        &self,
+        block: &Block,
+        prevouts: &[Vec<u8>],
+        prev_header: &sha256d::Hash,
+    ) -> sha256d::Hash {
+        let filter = BlockFilter::new(block, prevouts);
+        self.append(&block.block_hash(), &filter, prev_header)
+    }
+
+    /// Stores `filter` for `block_hash` and extends the header chain from
+    /// `prev_header`, returning the new filter header.
+    pub fn append(
+        &self,
+        block_hash: &BlockHash,
+        filter: &BlockFilter,
+        prev_header: &sha256d::Hash,
+    ) -> sha256d::Hash {
+        let header = filter.filter_header(prev_header);
+        self.filters()
+            .set(&block_hash.as_ref(), &filter.content)
+            .expect("Failed to persist filter");
+        self.headers()
+            .set(&block_hash.as_ref(), &header.to_byte_array().to_vec())
+            .expect("Failed to persist filter header");
+        header
+    }
+
+    /// Returns the stored filter for `block_hash`, if any.
+    pub fn get_filter(&self, block_hash: &BlockHash) -> Option<BlockFilter> {
+        self.filters()
+            .get(&block_hash.as_ref())
+            .expect("Failed to read filter")
+            .map(|content| BlockFilter { content })
+    }
+
+    /// Returns the stored filter header for `block_hash`, if any.
+    pub fn get_header(&self, block_hash: &BlockHash) -> Option<sha256d::Hash> {
+        self.headers()
+            .get(&block_hash.as_ref())
+            .expect("Failed to read filter header")
+            .map(|bytes| sha256d::Hash::from_slice(&bytes).expect("corrupt filter header"))
+    }
+}
@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: MIT
+
+//! The interface the [`Prover`](crate::prover::Prover) uses to talk to the
+//! underlying Bitcoin data source. Every backend - `bitcoind` over RPC,
+//! esplora over HTTP, or electrum over TCP - implements this trait so the rest
+//! of the bridge doesn't care where the blocks come from.
+
+use std::sync::Arc;
+
+use bitcoin::block::Header as BlockHeader;
+use bitcoin::Block;
+use bitcoin::BlockHash;
+use serde::Serialize;
+
+/// A coarse fee priority a client can ask for, mapped to a confirmation
+/// target by [`Blockchain::fee_for_tier`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeTier {
+    /// Cheap, may wait a while to confirm (~6 blocks).
+    Background,
+    /// A balanced default (~3 blocks).
+    Normal,
+    /// Confirm as soon as possible (next block).
+    HighPriority,
+}
+
+impl FeeTier {
+    /// The confirmation target, in blocks, this tier asks the backend for.
+    pub fn target(self) -> u16 {
+        match self {
+            FeeTier::Background => 6,
+            FeeTier::Normal => 3,
+            FeeTier::HighPriority => 1,
+        }
+    }
+}
+
+/// A fee rate in satoshis per virtual byte.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize)]
+pub struct FeeRate(pub f64);
+
+/// The fee data the `api` serves to clients that have no full node of their
+/// own, mirroring the tiers and mempool minimum the LDK bitcoind client
+/// exposes to its callers. Every rate is already floored at the mempool
+/// minimum relay fee.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct FeeEstimates {
+    /// Cheap, may wait a while to confirm.
+    pub background: FeeRate,
+    /// A balanced default.
+    pub normal: FeeRate,
+    /// Confirm as soon as possible.
+    pub high_priority: FeeRate,
+    /// The current mempool minimum relay fee, the floor on every tier.
+    pub mempool_min: FeeRate,
+}
+
+/// Lets a shared [`Arc`] stand in for its backend, so the same source can be
+/// handed to both the prover and the api.
+impl<B: Blockchain + ?Sized> Blockchain for Arc<B> {
+    type Error = B::Error;
+
+    fn get_block_count(&self) -> Result<u64, Self::Error> {
+        (**self).get_block_count()
+    }
+
+    fn get_block_hash(&self, height: u64) -> Result<BlockHash, Self::Error> {
+        (**self).get_block_hash(height)
+    }
+
+    fn get_block_header(&self, hash: &BlockHash) -> Result<BlockHeader, Self::Error> {
+        (**self).get_block_header(hash)
+    }
+
+    fn get_block(&self, hash: &BlockHash) -> Result<Block, Self::Error> {
+        (**self).get_block(hash)
+    }
+
+    fn wait_for_new_block(&self) -> Result<u64, Self::Error> {
+        (**self).wait_for_new_block()
+    }
+
+    fn estimate_fee(&self, target: u16) -> Result<FeeRate, Self::Error> {
+        (**self).estimate_fee(target)
+    }
+
+    fn mempool_min_fee(&self) -> Result<FeeRate, Self::Error> {
+        (**self).mempool_min_fee()
+    }
+}
+
+/// Collects the per-tier fee estimates and the mempool minimum from `source`.
+/// This is what the `api` JSON-RPC fee method returns, so clients syncing via
+/// the bridge can get fee data from the same endpoint.
+pub fn fee_estimates<B: Blockchain>(source: &B) -> Result<FeeEstimates, B::Error> {
+    Ok(FeeEstimates {
+        background: source.fee_for_tier(FeeTier::Background)?,
+        normal: source.fee_for_tier(FeeTier::Normal)?,
+        high_priority: source.fee_for_tier(FeeTier::HighPriority)?,
+        mempool_min: source.mempool_min_fee()?,
+    })
+}
+
+/// A source of block and header data for the bridge.
+///
+/// Implementors expose enough of the chain to let the prover walk it forwards:
+/// the current tip, a block hash for a given height, the header for
+/// `chainview`, the full block for the `BlocksFileManager`, and a way to wait
+/// for a new tip. They also provide the fee estimate and mempool minimum fee
+/// that the `api` serves.
+pub trait Blockchain {
+    /// The error type returned by this backend.
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Returns the height of the best block the backend currently knows about.
+    fn get_block_count(&self) -> Result<u64, Self::Error>;
+
+    /// Returns the block hash at `height` in the best chain.
+    fn get_block_hash(&self, height: u64) -> Result<BlockHash, Self::Error>;
+
+    /// Returns the header for `hash`, used to feed `chainview`.
+    fn get_block_header(&self, hash: &BlockHash) -> Result<BlockHeader, Self::Error>;
+
+    /// Returns the full serialized block for `hash`, stored by the
+    /// `BlocksFileManager` and served to peers verbatim.
+    fn get_block(&self, hash: &BlockHash) -> Result<Block, Self::Error>;
+
+    /// Blocks until the backend learns of a new tip, returning its height.
+    ///
+    /// Backends that can't push new blocks (electrum, esplora) implement this
+    /// as a subscription where available and otherwise poll.
+    fn wait_for_new_block(&self) -> Result<u64, Self::Error>;
+
+    /// Estimates the fee rate needed to confirm within `target` blocks, the
+    /// `estimatesmartfee` equivalent for this backend.
+    fn estimate_fee(&self, target: u16) -> Result<FeeRate, Self::Error>;
+
+    /// The current mempool minimum relay fee rate. Clients should never bid
+    /// below this, so it acts as a floor on every estimate.
+    fn mempool_min_fee(&self) -> Result<FeeRate, Self::Error>;
+
+    /// Estimates the fee rate for a coarse [`FeeTier`], floored at the mempool
+    /// minimum relay fee. This is what the `api` serves to clients that have no
+    /// full node of their own.
+    fn fee_for_tier(&self, tier: FeeTier) -> Result<FeeRate, Self::Error> {
+        let estimate = self.estimate_fee(tier.target())?;
+        let floor = self.mempool_min_fee()?;
+        Ok(FeeRate(estimate.0.max(floor.0)))
+    }
+}
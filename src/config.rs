@@ -0,0 +1,223 @@
+// SPDX-License-Identifier: MIT
+
+//! Runtime configuration for the bridge.
+//!
+//! Everything that used to be hardcoded in `main` - the data directory, the
+//! network, the block source and its credentials, the p2p and API listeners,
+//! and the logger - lives here. Values are read from a TOML file and may be
+//! overridden on the command line; on first run a default file is written and
+//! the data directory is created.
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use bitcoin::Network;
+use clap::Parser;
+use clap::ValueEnum;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Which backend the prover pulls blocks from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+#[clap(rename_all = "lowercase")]
+pub enum BlockSource {
+    /// A local `bitcoind` over JSON-RPC.
+    Bitcoind,
+    /// A remote esplora HTTP endpoint.
+    Esplora,
+    /// A remote Electrum server.
+    Electrum,
+}
+
+/// Connection parameters for the selected [`BlockSource`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceConfig {
+    /// Which backend to use.
+    pub kind: BlockSource,
+    /// RPC url for `bitcoind`, or the base url for esplora/electrum.
+    pub url: String,
+    /// Path to the `bitcoind` cookie file, if cookie auth is used.
+    #[serde(default)]
+    pub cookie: Option<PathBuf>,
+    /// `user:password` for `bitcoind`, if not using a cookie.
+    #[serde(default)]
+    pub userpass: Option<String>,
+}
+
+/// Look-ahead block-download pipeline settings used during initial sync.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConfig {
+    /// Number of worker tasks fetching blocks concurrently.
+    pub workers: usize,
+    /// How far ahead of the consumer the reorder buffer may run. Bounds the
+    /// number of in-flight blocks and therefore memory use.
+    pub lookahead: usize,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            workers: 8,
+            lookahead: 32,
+        }
+    }
+}
+
+/// Logger settings covering the old `init_logger` TODO.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogConfig {
+    /// One of `error`, `warn`, `info`, `debug`, `trace`.
+    pub level: String,
+    /// File to write logs to, relative to the data directory.
+    pub file: PathBuf,
+}
+
+/// The fully resolved configuration the rest of the bridge consumes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    /// The Bitcoin network to follow.
+    pub network: Network,
+    /// Directory holding the databases, blocks and logs.
+    pub datadir: PathBuf,
+    /// Address the p2p node listens on.
+    pub p2p_address: SocketAddr,
+    /// Address the JSON-RPC API listens on.
+    pub api_address: SocketAddr,
+    // TOML can't represent a bare value after a table at the same level, so
+    // every scalar field must come before the `source`/`log`/`sync` tables or
+    // `toml::to_string_pretty` fails with `ValueAfterTable`.
+    /// The block source and its connection parameters.
+    pub source: SourceConfig,
+    /// Logger settings.
+    pub log: LogConfig,
+    /// Initial-sync download pipeline settings.
+    #[serde(default)]
+    pub sync: SyncConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            network: Network::Signet,
+            datadir: default_datadir(),
+            source: SourceConfig {
+                kind: BlockSource::Bitcoind,
+                url: "localhost:38332".into(),
+                cookie: Some(
+                    dirs_home().join(".bitcoin").join("signet").join(".cookie"),
+                ),
+                userpass: None,
+            },
+            p2p_address: "0.0.0.0:28333".parse().unwrap(),
+            api_address: "127.0.0.1:3333".parse().unwrap(),
+            log: LogConfig {
+                level: "info".into(),
+                file: "debug.log".into(),
+            },
+            sync: SyncConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    /// The absolute path of the logger's output file.
+    pub fn log_file(&self) -> PathBuf {
+        self.datadir.join(&self.log.file)
+    }
+
+    /// A subpath inside the data directory, replacing the old `subdir!` macro.
+    pub fn subdir(&self, path: &str) -> PathBuf {
+        self.datadir.join(path)
+    }
+}
+
+/// Command-line arguments. Any value given here overrides the config file.
+#[derive(Debug, Parser)]
+#[command(about = "A Utreexo bridge node", long_about = None)]
+pub struct Cli {
+    /// Path to the TOML config file.
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+    /// Override the network.
+    #[arg(long)]
+    pub network: Option<Network>,
+    /// Override the data directory.
+    #[arg(long)]
+    pub datadir: Option<PathBuf>,
+    /// Override the block source.
+    #[arg(long)]
+    pub source: Option<BlockSource>,
+    /// Override the block-source url.
+    #[arg(long)]
+    pub source_url: Option<String>,
+    /// Override the p2p listen address.
+    #[arg(long)]
+    pub p2p_address: Option<SocketAddr>,
+    /// Override the API listen address.
+    #[arg(long)]
+    pub api_address: Option<SocketAddr>,
+    /// Override the log level.
+    #[arg(long)]
+    pub log_level: Option<String>,
+}
+
+impl Config {
+    /// Loads the configuration: reads the file (writing a default one on first
+    /// run), applies CLI overrides, then ensures the data directory exists.
+    pub fn load(cli: &Cli) -> anyhow::Result<Self> {
+        let path = cli
+            .config
+            .clone()
+            .unwrap_or_else(|| default_datadir().join("bridge.toml"));
+
+        let mut config = if path.exists() {
+            let raw = std::fs::read_to_string(&path)
+                .with_context(|| format!("reading config {}", path.display()))?;
+            toml::from_str(&raw).with_context(|| format!("parsing config {}", path.display()))?
+        } else {
+            let config = Config::default();
+            std::fs::create_dir_all(&config.datadir)
+                .with_context(|| format!("creating datadir {}", config.datadir.display()))?;
+            std::fs::write(&path, toml::to_string_pretty(&config)?)
+                .with_context(|| format!("writing default config {}", path.display()))?;
+            config
+        };
+
+        if let Some(network) = cli.network {
+            config.network = network;
+        }
+        if let Some(datadir) = &cli.datadir {
+            config.datadir = datadir.clone();
+        }
+        if let Some(source) = cli.source {
+            config.source.kind = source;
+        }
+        if let Some(url) = &cli.source_url {
+            config.source.url = url.clone();
+        }
+        if let Some(addr) = cli.p2p_address {
+            config.p2p_address = addr;
+        }
+        if let Some(addr) = cli.api_address {
+            config.api_address = addr;
+        }
+        if let Some(level) = &cli.log_level {
+            config.log.level = level.clone();
+        }
+
+        std::fs::create_dir_all(&config.datadir)
+            .with_context(|| format!("creating datadir {}", config.datadir.display()))?;
+
+        Ok(config)
+    }
+}
+
+fn dirs_home() -> PathBuf {
+    PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| ".".into()))
+}
+
+fn default_datadir() -> PathBuf {
+    dirs_home().join(".bridge")
+}
@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: MIT
+
+//! A [`Blockchain`](crate::chaininterface::Blockchain) backend backed by an
+//! Electrum/ElectrumX/Fulcrum server.
+//!
+//! This lets operators drive the bridge against a remote Electrum connection
+//! instead of a local `bitcoind` with the RPC port open. Electrum doesn't
+//! stream blocks, so we use `blockchain.headers.subscribe` to learn of new
+//! tips and fall back to polling when the subscription is quiet.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use bitcoin::block::Header as BlockHeader;
+use bitcoin::consensus::deserialize;
+use bitcoin::Block;
+use bitcoin::BlockHash;
+use electrum_client::Client;
+use electrum_client::ElectrumApi;
+use log::debug;
+
+use crate::chaininterface::Blockchain;
+use crate::chaininterface::FeeRate;
+
+/// Conversion from BTC per kilobyte (what Electrum reports) to satoshis per
+/// virtual byte (what the api serves).
+const BTC_PER_KVB_TO_SAT_PER_VB: f64 = 100_000.0;
+
+/// How long we sleep between tip polls when the header subscription hasn't
+/// produced anything new.
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// A block source that talks to an Electrum server.
+pub struct ElectrumBlockchain {
+    client: Client,
+    /// The last tip height we reported from [`wait_for_new_block`], so polling
+    /// can tell a genuinely new block from a repeated subscription reply.
+    ///
+    /// [`wait_for_new_block`]: Blockchain::wait_for_new_block
+    last_tip: Mutex<u64>,
+    /// Maps a block hash back to its height. Electrum only indexes headers by
+    /// height, and `chainview` asks for headers by hash, so we remember the
+    /// mapping as we walk the chain instead of scanning every height per
+    /// lookup.
+    heights: Mutex<HashMap<BlockHash, u64>>,
+}
+
+impl ElectrumBlockchain {
+    /// Connects to the Electrum server at `url` (e.g. `tcp://localhost:50001`
+    /// or `ssl://electrum.example.com:50002`) and subscribes to header
+    /// notifications so we can be woken on new tips.
+    pub fn new(url: &str) -> Result<Self, electrum_client::Error> {
+        let client = Client::new(url)?;
+        let tip = client.block_headers_subscribe()?.height as u64;
+        Ok(Self {
+            client,
+            last_tip: Mutex::new(tip),
+            heights: Mutex::new(HashMap::new()),
+        })
+    }
+}
+
+impl Blockchain for ElectrumBlockchain {
+    type Error = electrum_client::Error;
+
+    fn get_block_count(&self) -> Result<u64, Self::Error> {
+        Ok(self.client.block_headers_subscribe()?.height as u64)
+    }
+
+    fn get_block_hash(&self, height: u64) -> Result<BlockHash, Self::Error> {
+        let header = self.client.block_header(height as usize)?;
+        let hash = header.block_hash();
+        // Remember the mapping so `get_block_header` can resolve this hash in
+        // one round-trip instead of scanning every height.
+        self.heights.lock().unwrap().insert(hash, height);
+        Ok(hash)
+    }
+
+    fn get_block_header(&self, hash: &BlockHash) -> Result<BlockHeader, Self::Error> {
+        // Electrum indexes headers by height, not by hash. In the common case
+        // the height was recorded by `get_block_hash` as we walked up and the
+        // lookup is a single round-trip.
+        if let Some(height) = self.heights.lock().unwrap().get(hash).copied() {
+            return self.client.block_header(height as usize);
+        }
+        // Cold cache - e.g. just after a restart, when the prover resumes from
+        // its stored tip and asks `chainview` for a header by a hash it hasn't
+        // re-walked this run. Resolve the height independently by walking
+        // headers down from the tip, warming the cache as we go so later
+        // lookups are O(1) again. Electrum offers no by-hash header lookup, so
+        // this one-time scan is the only way to recover the mapping.
+        let tip = self.get_block_count()?;
+        for height in (0..=tip).rev() {
+            let header = self.client.block_header(height as usize)?;
+            self.heights.lock().unwrap().insert(header.block_hash(), height);
+            if header.block_hash() == *hash {
+                return Ok(header);
+            }
+        }
+        Err(electrum_client::Error::Message(format!(
+            "unknown block header {hash}"
+        )))
+    }
+
+    fn get_block(&self, hash: &BlockHash) -> Result<Block, Self::Error> {
+        // `blockchain.block.get` returns the raw hex-encoded block; the server
+        // must be running with full blocks enabled (e.g. Fulcrum).
+        let raw = self.client.raw_call(
+            "blockchain.block.get",
+            vec![electrum_client::raw_client::Param::String(hash.to_string())],
+        )?;
+        let hex = raw
+            .as_str()
+            .ok_or_else(|| electrum_client::Error::Message("malformed block response".into()))?;
+        let bytes = hex::decode(hex)
+            .map_err(|e| electrum_client::Error::Message(format!("invalid block hex: {e}")))?;
+        deserialize(&bytes)
+            .map_err(|e| electrum_client::Error::Message(format!("invalid block: {e}")))
+    }
+
+    fn wait_for_new_block(&self) -> Result<u64, Self::Error> {
+        let mut last_tip = self.last_tip.lock().unwrap();
+        loop {
+            // Drain any buffered subscription notifications first, then fall
+            // back to an explicit re-subscribe poll.
+            while let Some(header) = self.client.block_headers_pop()? {
+                if header.height as u64 > *last_tip {
+                    *last_tip = header.height as u64;
+                    return Ok(*last_tip);
+                }
+            }
+            let tip = self.client.block_headers_subscribe()?.height as u64;
+            if tip > *last_tip {
+                *last_tip = tip;
+                return Ok(tip);
+            }
+            debug!("electrum tip unchanged at {tip}, polling again");
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    fn estimate_fee(&self, target: u16) -> Result<FeeRate, Self::Error> {
+        // `blockchain.estimatefee` returns BTC/kB, or -1 when the server can't
+        // estimate yet; fall back to the relay fee in that case.
+        let btc_per_kvb = self.client.estimate_fee(target as usize)?;
+        if btc_per_kvb <= 0.0 {
+            return self.mempool_min_fee();
+        }
+        Ok(FeeRate(btc_per_kvb * BTC_PER_KVB_TO_SAT_PER_VB))
+    }
+
+    fn mempool_min_fee(&self) -> Result<FeeRate, Self::Error> {
+        Ok(FeeRate(self.client.relay_fee()? * BTC_PER_KVB_TO_SAT_PER_VB))
+    }
+}
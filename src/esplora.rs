@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: MIT
+
+//! A [`Blockchain`](crate::chaininterface::Blockchain) backend backed by a
+//! remote esplora HTTP endpoint.
+//!
+//! esplora doesn't stream blocks either, so new tips are discovered by polling
+//! the height endpoint.
+
+use std::collections::HashMap;
+
+use bitcoin::block::Header as BlockHeader;
+use bitcoin::Block;
+use bitcoin::BlockHash;
+use esplora_client::BlockingClient;
+use esplora_client::Builder;
+
+use crate::chaininterface::Blockchain;
+use crate::chaininterface::FeeRate;
+
+/// How long we sleep between tip polls when the chain hasn't advanced.
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// The default floor, in satoshis per virtual byte, used when the endpoint
+/// can't give us a minimum relay fee.
+const DEFAULT_MIN_FEE: f64 = 1.0;
+
+/// A block source that talks to an esplora server.
+pub struct EsploraBlockchain {
+    client: BlockingClient,
+}
+
+impl EsploraBlockchain {
+    /// Builds a blocking esplora client for the endpoint at `url` (e.g.
+    /// `https://mempool.space/signet/api`).
+    pub fn new(url: String) -> Self {
+        let client = Builder::new(&url)
+            .build_blocking()
+            .expect("Failed to build the esplora client");
+        Self { client }
+    }
+
+    /// Returns the fee estimates (confirmation target in blocks to fee rate in
+    /// satoshis per virtual byte) reported by the endpoint.
+    fn fee_estimates(&self) -> Result<HashMap<u16, f64>, esplora_client::Error> {
+        Ok(self
+            .client
+            .get_fee_estimates()?
+            .into_iter()
+            .filter_map(|(target, rate)| target.parse().ok().map(|t| (t, rate)))
+            .collect())
+    }
+}
+
+impl Blockchain for EsploraBlockchain {
+    type Error = esplora_client::Error;
+
+    fn get_block_count(&self) -> Result<u64, Self::Error> {
+        Ok(self.client.get_height()? as u64)
+    }
+
+    fn get_block_hash(&self, height: u64) -> Result<BlockHash, Self::Error> {
+        self.client.get_block_hash(height as u32)
+    }
+
+    fn get_block_header(&self, hash: &BlockHash) -> Result<BlockHeader, Self::Error> {
+        self.client.get_header_by_hash(hash)
+    }
+
+    fn get_block(&self, hash: &BlockHash) -> Result<Block, Self::Error> {
+        self.client
+            .get_block_by_hash(hash)?
+            .ok_or(esplora_client::Error::HeaderHeightNotFound(0))
+    }
+
+    fn wait_for_new_block(&self) -> Result<u64, Self::Error> {
+        let start = self.get_block_count()?;
+        loop {
+            let tip = self.get_block_count()?;
+            if tip > start {
+                return Ok(tip);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    fn estimate_fee(&self, target: u16) -> Result<FeeRate, Self::Error> {
+        let estimates = self.fee_estimates()?;
+        // esplora only publishes estimates for a handful of targets, so pick
+        // the tightest one no looser than what we asked for.
+        let rate = estimates
+            .iter()
+            .filter(|(t, _)| **t <= target)
+            .max_by_key(|(t, _)| **t)
+            .map(|(_, rate)| *rate)
+            .unwrap_or(DEFAULT_MIN_FEE);
+        Ok(FeeRate(rate))
+    }
+
+    fn mempool_min_fee(&self) -> Result<FeeRate, Self::Error> {
+        // esplora has no explicit mempool-min-fee endpoint; the loosest
+        // published estimate is the closest proxy for the relay floor.
+        let estimates = self.fee_estimates()?;
+        let rate = estimates
+            .into_iter()
+            .max_by_key(|(t, _)| *t)
+            .map(|(_, rate)| rate)
+            .unwrap_or(DEFAULT_MIN_FEE);
+        Ok(FeeRate(rate))
+    }
+}
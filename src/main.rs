@@ -1,45 +1,56 @@
 //SPDX-License-Identifier: MIT
 
 mod api;
+mod cfilters;
 mod chaininterface;
 mod chainview;
+mod config;
+#[cfg(feature = "electrum")]
+mod electrum;
 #[cfg(feature = "esplora")]
 mod esplora;
 mod node;
+mod pipeline;
 mod prove;
 mod prover;
+mod rpc;
 mod udata;
 
 use std::{
-    env,
+    str::FromStr,
     sync::{Arc, Mutex},
 };
 
 use actix_rt::signal::ctrl_c;
-#[cfg(not(feature = "esplora"))]
 use bitcoincore_rpc::{Auth, Client};
 
+use clap::Parser;
 use futures::channel::mpsc::channel;
 use log::{info, warn};
 use prove::{BlocksFileManager, BlocksIndex};
-use simplelog::{Config, SharedLogger};
+use simplelog::{Config as LogFormat, SharedLogger};
 
+use crate::cfilters::FiltersIndex;
+use crate::chaininterface::Blockchain;
+use crate::chainview::ChainView;
+use crate::config::{BlockSource, Cli, Config};
 use crate::node::Node;
 
 fn main() -> anyhow::Result<()> {
-    // Initialize the logger
-    // TODO: make this configurable
+    let cli = Cli::parse();
+    let config = Config::load(&cli)?;
+
+    // Initialize the logger from the config
     init_logger(
-        Some(subdir!("debug.log")),
-        simplelog::LevelFilter::Info,
+        Some(config.log_file()),
+        log::LevelFilter::from_str(&config.log.level).unwrap_or(log::LevelFilter::Info),
         true,
     );
-    // let client = esplora::EsploraBlockchain::new("https://mempool.space/signet/api".into());
     // Create a chainview, this module will download headers from the bitcoin core
     // to keep track of the current chain state and speed up replying to headers requests
     // from peers.
     let store = kv::Store::new(kv::Config {
-        path: subdir!("chain_view").into(),
+        path: config.subdir("chain_view"),
         temporary: false,
         use_compression: false,
         flush_every_ms: None,
@@ -54,7 +65,7 @@ fn main() -> anyhow::Result<()> {
     // the blocks themselves
     let index_store = BlocksIndex {
         database: kv::Store::new(kv::Config {
-            path: subdir!("index/").into(),
+            path: config.subdir("index/"),
             temporary: false,
             use_compression: false,
             flush_every_ms: None,
@@ -65,37 +76,98 @@ fn main() -> anyhow::Result<()> {
     };
     // Put it into an Arc so we can share it between threads
     let index_store = Arc::new(index_store);
+    // This database stores the BIP158 compact filters and the filter-header
+    // chain, so the node can serve `getcfilters`/`getcfheaders`/`getcfcheckpt`
+    // to light clients. Filters are generated in `prover.keep_up` as blocks
+    // are persisted.
+    let filters_store = Arc::new(cfilters::FiltersIndex {
+        database: kv::Store::new(kv::Config {
+            path: config.subdir("filters/"),
+            temporary: false,
+            use_compression: false,
+            flush_every_ms: None,
+            cache_capacity: None,
+            segment_size: None,
+        })
+        .unwrap(),
+    });
     // This database stores the blocks themselves, it's a collection of flat files
     // that are indexed by the index above. They are stored in the `blocks/` directory
     // and are serialized as bitcoin blocks, so we don't need to do any parsing
     // before sending to a peer.
     let blocks = Arc::new(Mutex::new(BlocksFileManager::new()));
-    // Create a prover, this module will download blocks from the bitcoin core
-    // node and save them to disk. It will also create proofs for the blocks
-    // and save them to disk.
-    // Create a json-rpc client to bitcoin core
-    #[cfg(not(feature = "esplora"))]
-    let mut prover = {
-        let cookie = env!("HOME").to_owned() + "/.bitcoin/signet/.cookie";
-        let client = Client::new(
-            "localhost:38332".into(),
-            Auth::CookieFile(cookie.clone().into()),
-        )
-        .unwrap();
 
-        prover::Prover::new(client, index_store.clone(), blocks.clone(), view.clone())
-    };
-    #[cfg(feature = "esplora")]
-    let mut prover = {
-        let client = esplora::EsploraBlockchain::new("https://mempool.space/signet/api".into());
-        prover::Prover::new(client, index_store.clone(), blocks.clone(), view.clone())
-    };
+    // Select the block source at runtime from the config. Each backend is only
+    // available if its feature was compiled in; asking for one that wasn't is a
+    // clear error rather than a silently ignored setting.
+    match config.source.kind {
+        BlockSource::Bitcoind => {
+            let auth = match (&config.source.cookie, &config.source.userpass) {
+                (Some(cookie), _) => Auth::CookieFile(cookie.clone()),
+                (None, Some(userpass)) => {
+                    let (user, pass) = userpass
+                        .split_once(':')
+                        .expect("source.userpass must be `user:password`");
+                    Auth::UserPass(user.to_owned(), pass.to_owned())
+                }
+                (None, None) => Auth::None,
+            };
+            let client = Client::new(&config.source.url, auth)?;
+            run(&config, client, index_store, blocks, filters_store, view)
+        }
+        BlockSource::Esplora => {
+            #[cfg(feature = "esplora")]
+            {
+                let client = esplora::EsploraBlockchain::new(config.source.url.clone());
+                run(&config, client, index_store, blocks, filters_store, view)
+            }
+            #[cfg(not(feature = "esplora"))]
+            anyhow::bail!("this build was compiled without the `esplora` backend");
+        }
+        BlockSource::Electrum => {
+            #[cfg(feature = "electrum")]
+            {
+                let client = electrum::ElectrumBlockchain::new(&config.source.url)
+                    .expect("Failed to connect to the Electrum server");
+                run(&config, client, index_store, blocks, filters_store, view)
+            }
+            #[cfg(not(feature = "electrum"))]
+            anyhow::bail!("this build was compiled without the `electrum` backend");
+        }
+    }
+}
+
+/// Wires a chosen block source into the prover, p2p node and api, then runs the
+/// prover until it's asked to stop. Generic over the backend so the same
+/// startup path serves every [`BlockSource`].
+fn run<B: Blockchain + Send + Sync + 'static>(
+    config: &Config,
+    client: B,
+    index_store: Arc<BlocksIndex>,
+    blocks: Arc<Mutex<BlocksFileManager>>,
+    filters_store: Arc<FiltersIndex>,
+    view: Arc<ChainView>,
+) -> anyhow::Result<()> {
+    // Create a prover, this module will download blocks from the configured
+    // block source and save them to disk. It will also create proofs for the
+    // blocks and save them to disk.
+    // Share the block source between the prover and the api so the api can
+    // serve fee estimates from the same backend.
+    let source = Arc::new(client);
+    let mut prover = prover::Prover::new(
+        source.clone(),
+        index_store.clone(),
+        blocks.clone(),
+        filters_store.clone(),
+        view.clone(),
+        config.sync.clone(),
+    );
 
     info!("Starting p2p node");
     // This is our implementation of the Bitcoin p2p protocol, it will listen
     // for incoming connections and serve blocks and proofs to peers.
-    let listener = std::net::TcpListener::bind("0.0.0.0:28333").unwrap();
-    let node = node::Node::new(listener, blocks, index_store, view);
+    let listener = std::net::TcpListener::bind(config.p2p_address).unwrap();
+    let node = node::Node::new(listener, blocks, index_store, filters_store, view);
     std::thread::spawn(move || {
         Node::accept_connections(node);
     });
@@ -103,9 +175,11 @@ fn main() -> anyhow::Result<()> {
     // This is our implementation of the json-rpc api, it will listen for
     // incoming connections and serve some Utreexo data to clients.
     info!("Starting api");
-    std::thread::spawn(|| {
+    let api_address = config.api_address;
+    let api_source = source.clone();
+    std::thread::spawn(move || {
         actix_rt::System::new()
-            .block_on(api::create_api(sender))
+            .block_on(api::create_api(sender, api_address, api_source))
             .unwrap()
     });
 
@@ -126,19 +200,12 @@ fn main() -> anyhow::Result<()> {
     prover.keep_up(kill_signal2, receiver)
 }
 
-macro_rules! subdir {
-    ($path:expr) => {
-        concat!(env!("HOME"), "/.bridge/", $path)
-    };
-}
-pub(crate) use subdir;
-
-fn init_logger(log_file: Option<&str>, log_level: log::LevelFilter, log_to_term: bool) {
+fn init_logger(log_file: Option<std::path::PathBuf>, log_level: log::LevelFilter, log_to_term: bool) {
     let mut loggers: Vec<Box<dyn SharedLogger>> = vec![];
     if let Some(file) = log_file {
         let file_logger = simplelog::WriteLogger::new(
             log_level,
-            Config::default(),
+            LogFormat::default(),
             std::fs::File::create(file).unwrap(),
         );
         loggers.push(file_logger);
@@ -146,7 +213,7 @@ fn init_logger(log_file: Option<&str>, log_level: log::LevelFilter, log_to_term:
     if log_to_term {
         let term_logger = simplelog::TermLogger::new(
             log_level,
-            Config::default(),
+            LogFormat::default(),
             simplelog::TerminalMode::Mixed,
             simplelog::ColorChoice::Auto,
         );
@@ -0,0 +1,171 @@
+// SPDX-License-Identifier: MIT
+
+//! Bounded look-ahead block-download pipeline for initial sync.
+//!
+//! `prover.keep_up` proves blocks strictly in order because the Utreexo
+//! accumulator update is sequential, but fetching them one at a time starves
+//! the link when the block source is a remote esplora/electrum endpoint. This
+//! module fans the fetch out to a pool of workers that download a window of
+//! heights concurrently into a reorder buffer, while the single consumer pulls
+//! blocks in height order. The buffer is capped at `lookahead` blocks, so
+//! workers block once they run ahead and memory stays bounded.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Condvar;
+use std::sync::Mutex;
+
+use bitcoin::Block;
+use log::warn;
+
+use crate::chaininterface::Blockchain;
+
+/// How long a worker waits before retrying a fetch that failed with a transient
+/// error, so a flaky link doesn't spin the CPU.
+const RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Drives a pool of fetch workers against a [`Blockchain`] and hands blocks to
+/// the caller in strict height order.
+pub struct BlockPipeline<B: Blockchain> {
+    source: Arc<B>,
+    workers: usize,
+    lookahead: usize,
+}
+
+/// Shared state between the workers and the consumer.
+struct Shared {
+    /// Blocks fetched but not yet consumed, keyed by height.
+    buffer: Mutex<BTreeMap<u64, Block>>,
+    /// Next height a worker should claim.
+    next_to_fetch: AtomicU64,
+    /// Lowest height the consumer has not yet handed out. Backpressure is gated
+    /// on this real position rather than the buffer contents, which can drain
+    /// to empty while the consumer is still behind.
+    next_to_consume: AtomicU64,
+    /// One past the last height in this sync window.
+    end: u64,
+    /// Signalled when a block is inserted or removed from the buffer.
+    cond: Condvar,
+}
+
+impl<B: Blockchain + Send + Sync + 'static> BlockPipeline<B> {
+    /// Builds a pipeline fetching from `source` with the given worker count and
+    /// look-ahead depth (see [`SyncConfig`](crate::config::SyncConfig)).
+    pub fn new(source: Arc<B>, workers: usize, lookahead: usize) -> Self {
+        Self {
+            source,
+            workers: workers.max(1),
+            lookahead: lookahead.max(1),
+        }
+    }
+
+    /// Downloads blocks in `start..end` concurrently and calls `consume` with
+    /// each one in ascending height order. Returns once every height has been
+    /// consumed.
+    pub fn sync<F>(&self, start: u64, end: u64, mut consume: F)
+    where
+        F: FnMut(u64, Block),
+    {
+        if start >= end {
+            return;
+        }
+
+        let shared = Arc::new(Shared {
+            buffer: Mutex::new(BTreeMap::new()),
+            next_to_fetch: AtomicU64::new(start),
+            next_to_consume: AtomicU64::new(start),
+            end,
+            cond: Condvar::new(),
+        });
+
+        let mut handles = Vec::with_capacity(self.workers);
+        for _ in 0..self.workers {
+            let shared = shared.clone();
+            let source = self.source.clone();
+            let lookahead = self.lookahead;
+            handles.push(std::thread::spawn(move || {
+                worker(&source, &shared, lookahead);
+            }));
+        }
+
+        // Consumer: pull blocks strictly in order as they become available.
+        for height in start..end {
+            let block = {
+                let mut buffer = shared.buffer.lock().unwrap();
+                let block = loop {
+                    if let Some(block) = buffer.remove(&height) {
+                        break block;
+                    }
+                    buffer = shared.cond.wait(buffer).unwrap();
+                };
+                // Advance the consumer position under the buffer lock, so a
+                // worker can't check it and then start waiting after the
+                // notification below has already been sent.
+                shared.next_to_consume.store(height + 1, Ordering::SeqCst);
+                block
+            };
+            // Wake any workers parked on backpressure now that a slot has
+            // freed up.
+            shared.cond.notify_all();
+            consume(height, block);
+        }
+
+        for handle in handles {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// A single fetch worker: claims heights, respects the look-ahead bound, and
+/// drops fetched blocks into the reorder buffer.
+fn worker<B: Blockchain>(source: &B, shared: &Shared, lookahead: usize) {
+    loop {
+        let height = shared.next_to_fetch.fetch_add(1, Ordering::SeqCst);
+        if height >= shared.end {
+            break;
+        }
+
+        // Backpressure: don't fetch more than `lookahead` blocks ahead of the
+        // consumer, so the buffer stays bounded. This is gated on the
+        // consumer's real position, not the buffer contents - the buffer often
+        // drains to empty while the consumer is still behind, and measuring
+        // from it would let a worker race ahead (or deadlock waiting on itself).
+        {
+            let buffer = shared.buffer.lock().unwrap();
+            let _unused = shared
+                .cond
+                .wait_while(buffer, |_| {
+                    height >= shared.next_to_consume.load(Ordering::SeqCst) + lookahead as u64
+                })
+                .unwrap();
+        }
+
+        // Fetch the block, retrying transient errors instead of dropping the
+        // height - the in-order consumer blocks forever on a height no worker
+        // ever produces, so a skipped height hangs the whole sync.
+        let block = fetch_block(source, height);
+        let mut buffer = shared.buffer.lock().unwrap();
+        buffer.insert(height, block);
+        shared.cond.notify_all();
+    }
+}
+
+/// Fetches the block at `height`, retrying on error until it succeeds. A
+/// permanently failing height can't simply be skipped: the ordered consumer
+/// would wait on it indefinitely.
+fn fetch_block<B: Blockchain>(source: &B, height: u64) -> Block {
+    loop {
+        let result = source
+            .get_block_hash(height)
+            .and_then(|hash| source.get_block(&hash));
+        match result {
+            Ok(block) => return block,
+            Err(e) => {
+                warn!("pipeline: failed to fetch block at height {height}: {e}, retrying");
+                std::thread::sleep(RETRY_INTERVAL);
+            }
+        }
+    }
+}
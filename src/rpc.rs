@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: MIT
+
+//! The [`Blockchain`](crate::chaininterface::Blockchain) backend backed by a
+//! local `bitcoind` over JSON-RPC.
+//!
+//! This is the default backend: the prover used to take a
+//! [`bitcoincore_rpc::Client`] directly, so we implement the trait on it rather
+//! than wrapping it, keeping the call sites in `main` unchanged.
+
+use bitcoin::block::Header as BlockHeader;
+use bitcoin::Block;
+use bitcoin::BlockHash;
+use bitcoincore_rpc::json::EstimateMode;
+use bitcoincore_rpc::Client;
+use bitcoincore_rpc::RpcApi;
+
+use crate::chaininterface::Blockchain;
+use crate::chaininterface::FeeRate;
+
+/// Conversion from BTC per kilobyte (what the fee RPCs report) to satoshis per
+/// virtual byte (what the api serves).
+const BTC_PER_KVB_TO_SAT_PER_VB: f64 = 100_000.0;
+
+/// How long we sleep between tip polls while waiting for a new block; bitcoind
+/// has no blocking "wait for block" RPC, so we poll.
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+impl Blockchain for Client {
+    type Error = bitcoincore_rpc::Error;
+
+    fn get_block_count(&self) -> Result<u64, Self::Error> {
+        RpcApi::get_block_count(self)
+    }
+
+    fn get_block_hash(&self, height: u64) -> Result<BlockHash, Self::Error> {
+        RpcApi::get_block_hash(self, height)
+    }
+
+    fn get_block_header(&self, hash: &BlockHash) -> Result<BlockHeader, Self::Error> {
+        RpcApi::get_block_header(self, hash)
+    }
+
+    fn get_block(&self, hash: &BlockHash) -> Result<Block, Self::Error> {
+        RpcApi::get_block(self, hash)
+    }
+
+    fn wait_for_new_block(&self) -> Result<u64, Self::Error> {
+        let start = RpcApi::get_block_count(self)?;
+        loop {
+            let tip = RpcApi::get_block_count(self)?;
+            if tip > start {
+                return Ok(tip);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    fn estimate_fee(&self, target: u16) -> Result<FeeRate, Self::Error> {
+        // `estimatesmartfee` reports BTC/kvB, or nothing when it can't estimate
+        // yet; fall back to the relay floor in that case.
+        let estimate = self.estimate_smart_fee(target, Some(EstimateMode::Conservative))?;
+        match estimate.fee_rate {
+            Some(amount) => Ok(FeeRate(amount.to_btc() * BTC_PER_KVB_TO_SAT_PER_VB)),
+            None => self.mempool_min_fee(),
+        }
+    }
+
+    fn mempool_min_fee(&self) -> Result<FeeRate, Self::Error> {
+        let info = self.get_mempool_info()?;
+        Ok(FeeRate(info.mempool_min_fee.to_btc() * BTC_PER_KVB_TO_SAT_PER_VB))
+    }
+}